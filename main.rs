@@ -1,5 +1,190 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
+// Quebra um texto em tokens para indexação/consulta: minúsculas e separado
+// por qualquer caractere que não seja letra ou dígito, para que "Mesa de
+// Madeira" produza os tokens ["mesa", "de", "madeira"].
+fn tokenize(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_string())
+        .collect()
+}
+
+// Distância de edição (Levenshtein) simples entre duas palavras, usada para
+// pontuar o quão "próxima" uma palavra do nome está de uma palavra da
+// consulta durante o ranqueamento.
+fn edit_distance(a: &str, b: &str) -> u32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<u32> = (0..=b.len() as u32).collect();
+
+    for i in 1..=a.len() {
+        let mut current_row = vec![i as u32; b.len() + 1];
+        for j in 1..=b.len() {
+            let substitution_cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            current_row[j] = (previous_row[j] + 1)
+                .min(current_row[j - 1] + 1)
+                .min(previous_row[j - 1] + substitution_cost);
+        }
+        previous_row = current_row;
+    }
+
+    previous_row[b.len()]
+}
+
+// Remove o diacrítico de uma letra já em minúsculas (ex.: 'ô' -> 'o'), para
+// que "Eletrônicos" e "eletronicos" normalizem para a mesma forma.
+fn strip_diacritic(c: char) -> char {
+    match c {
+        'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+        'ú' | 'ù' | 'û' | 'ü' => 'u',
+        'ç' => 'c',
+        'ñ' => 'n',
+        other => other,
+    }
+}
+
+// Assinatura de uma função de normalização: recebe um texto livre e devolve
+// a forma usada como chave de índice/comparação.
+type Normalizer = fn(&str) -> String;
+
+// Normalizador padrão: minúsculas e sem diacríticos, para que buscas sem
+// acento encontrem produtos cujo nome/categoria/marca tenham acento.
+fn default_normalizer(text: &str) -> String {
+    text.to_lowercase().chars().map(strip_diacritic).collect()
+}
+
+// Orçamento máximo de typos sensato para o tamanho de uma consulta, como a
+// maioria dos motores de busca faz: consultas muito curtas não toleram
+// edições, pois mesmo uma única edição já aproximaria praticamente qualquer
+// palavra da consulta.
+fn typo_budget_for_length(query_len: usize) -> u8 {
+    match query_len {
+        0..=4 => 0,
+        5..=8 => 1,
+        _ => 2,
+    }
+}
+
+// Filtro de categoria/marca: `Any` expressa explicitamente "sem restrição",
+// em vez de sobrecarregar a string vazia com esse significado.
+#[derive(Debug, Clone)]
+enum Filter {
+    Any,
+    Exact(String),
+}
+
+impl Filter {
+    // `Any` bate com qualquer valor; `Exact` exige igualdade com `value`.
+    // Deliberadamente um método à parte em vez de `impl PartialEq for Filter`:
+    // essa relação não é uma equivalência de verdade (ela não é transitiva —
+    // `Any` "bate" tanto com "a" quanto com "b", mas "a" != "b"), então
+    // sobrecarregar `==`/`Eq` violaria o contrato que essas traits prometem e
+    // seria uma armadilha para quem um dia guardar `Filter` num `HashSet`/
+    // `BTreeMap` ou usar `dedup`.
+    fn matches_value(&self, value: &str) -> bool {
+        match self {
+            Filter::Any => true,
+            Filter::Exact(expected) => expected == value,
+        }
+    }
+}
+
+// Metadados de casamento de um produto contra uma consulta, usados pelos
+// critérios de ranqueamento em `search_ranked`.
+#[derive(Debug, Clone, Copy, Default)]
+struct MatchMetrics {
+    // Quantas palavras da consulta casaram (exatamente ou por prefixo) com
+    // alguma palavra do nome.
+    matched_tokens: usize,
+    // Quantas dessas palavras casaram exatamente (em vez de só por prefixo).
+    exact_tokens: usize,
+    // Soma das distâncias de edição das palavras da consulta que não casaram
+    // nem exatamente nem por prefixo, até a palavra mais próxima do nome.
+    total_distance: u32,
+    // Posição (índice de palavra) do primeiro token da consulta encontrado no nome.
+    first_match_offset: usize,
+}
+
+// Um produto já casado, junto dos metadados usados para ordená-lo.
+struct RankedProduct {
+    product: Product,
+    metrics: MatchMetrics,
+}
+
+// Um critério de ranqueamento: compara dois produtos casados segundo um
+// único aspecto de relevância.
+trait Criterion {
+    fn compare(&self, a: &RankedProduct, b: &RankedProduct) -> Ordering;
+}
+
+// Mais palavras da consulta casadas é melhor.
+struct ByMatchedTokens;
+impl Criterion for ByMatchedTokens {
+    fn compare(&self, a: &RankedProduct, b: &RankedProduct) -> Ordering {
+        b.metrics.matched_tokens.cmp(&a.metrics.matched_tokens)
+    }
+}
+
+// Menos distância de edição acumulada é melhor.
+struct ByEditDistance;
+impl Criterion for ByEditDistance {
+    fn compare(&self, a: &RankedProduct, b: &RankedProduct) -> Ordering {
+        a.metrics.total_distance.cmp(&b.metrics.total_distance)
+    }
+}
+
+// Mais palavras casadas exatamente (em vez de só por prefixo) é melhor: um
+// casamento de "lap" com "laptop" por prefixo conta como `matched_tokens`,
+// mas perde para um casamento exato em "laptop" == "laptop".
+struct ByExactMatch;
+impl Criterion for ByExactMatch {
+    fn compare(&self, a: &RankedProduct, b: &RankedProduct) -> Ordering {
+        b.metrics.exact_tokens.cmp(&a.metrics.exact_tokens)
+    }
+}
+
+// Casamentos mais cedo no nome rankeiam melhor (ex.: a consulta bate na
+// primeira palavra do nome, não na última).
+struct ByWordPosition;
+impl Criterion for ByWordPosition {
+    fn compare(&self, a: &RankedProduct, b: &RankedProduct) -> Ordering {
+        a.metrics.first_match_offset.cmp(&b.metrics.first_match_offset)
+    }
+}
+
+// Uma cadeia ordenada de critérios, aplicada lexicograficamente: o primeiro
+// critério que distinguir dois produtos decide a ordem entre eles.
+struct Criteria(Vec<Box<dyn Criterion>>);
+
+impl Criteria {
+    // A ordenação padrão descrita no enunciado: nº de palavras casadas, soma
+    // de distância de edição, exatidão e posição da palavra.
+    fn default_criteria() -> Self {
+        Criteria(vec![
+            Box::new(ByMatchedTokens),
+            Box::new(ByEditDistance),
+            Box::new(ByExactMatch),
+            Box::new(ByWordPosition),
+        ])
+    }
+
+    fn compare(&self, a: &RankedProduct, b: &RankedProduct) -> Ordering {
+        for criterion in &self.0 {
+            let ordering = criterion.compare(a, b);
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    }
+}
+
 // Estrutura de um produto
 #[derive(Debug, Clone)]
 struct Product {
@@ -8,95 +193,324 @@ struct Product {
     brand: String,
 }
 
+// Estado de um autômato de Levenshtein: a linha de distâncias de edição
+// acumuladas entre a palavra consultada e o prefixo já consumido da entrada.
+#[derive(Debug, Clone)]
+struct LevenshteinState {
+    row: Vec<u8>,
+}
+
+// Autômato (DFA) de Levenshtein para uma consulta e distância máxima fixas.
+// Os estados são as linhas de distância; a transição consome um caractere
+// (por valor escalar Unicode, não por byte) e produz a próxima linha.
+struct LevenshteinDfa {
+    query: Vec<char>,
+    max_distance: u8,
+    prefix_mode: bool,
+}
+
+impl LevenshteinDfa {
+    fn start(&self) -> LevenshteinState {
+        LevenshteinState {
+            row: (0..=self.query.len() as u8).collect(),
+        }
+    }
+
+    fn step(&self, state: &LevenshteinState, ch: char) -> LevenshteinState {
+        let mut next = Vec::with_capacity(state.row.len());
+        next.push(state.row[0] + 1);
+        for j in 1..state.row.len() {
+            let substitution_cost = if self.query[j - 1] == ch { 0 } else { 1 };
+            let value = (state.row[j - 1] + substitution_cost)
+                .min(state.row[j] + 1)
+                .min(next[j - 1] + 1);
+            next.push(value);
+        }
+        LevenshteinState { row: next }
+    }
+
+    // Um estado é aceito quando a distância acumulada até a consulta inteira
+    // está dentro do limite configurado.
+    fn accepts(&self, state: &LevenshteinState) -> bool {
+        state
+            .row
+            .last()
+            .is_some_and(|&distance| distance <= self.max_distance)
+    }
+
+    // Avalia uma palavra inteira pelo autômato. Em modo prefixo, aceita assim
+    // que a consulta estiver dentro da distância de algum prefixo da palavra
+    // (permitindo que "Lap" combine com "Laptop"); caso contrário, só aceita
+    // ao final da palavra.
+    fn is_match(&self, word: &str) -> bool {
+        let mut state = self.start();
+        for ch in word.chars() {
+            state = self.step(&state, ch);
+            // Só checa depois de consumir ao menos um caractere da palavra:
+            // o estado inicial (prefixo vazio) não é testado no laço. Isso não
+            // impede que, com `query.len() <= max_distance`, qualquer palavra
+            // não vazia case: já após o primeiro caractere a distância fica
+            // dentro do limite (ver `search_fuzzy`).
+            if self.prefix_mode && self.accepts(&state) {
+                return true;
+            }
+        }
+        self.accepts(&state)
+    }
+}
+
+// Constrói autômatos de Levenshtein para uma distância máxima fixa. A
+// construção em si é barata aqui, mas mantemos um builder por distância para
+// que o chamador nunca precise recriá-lo por consulta.
+struct LevenshteinAutomatonBuilder {
+    max_distance: u8,
+}
+
+impl LevenshteinAutomatonBuilder {
+    fn new(max_distance: u8) -> Self {
+        LevenshteinAutomatonBuilder { max_distance }
+    }
+
+    fn build_dfa(&self, query: &str, prefix_mode: bool) -> LevenshteinDfa {
+        LevenshteinDfa {
+            query: query.chars().collect(),
+            max_distance: self.max_distance,
+            prefix_mode,
+        }
+    }
+}
+
 // Estrutura da loja com índices de busca
 struct Store {
     products: Vec<Product>,
     name_index: HashMap<String, Vec<usize>>,
     category_index: HashMap<String, Vec<usize>>,
     brand_index: HashMap<String, Vec<usize>>,
+    // Vocabulário de `name_index` ordenado, para localizar todas as palavras
+    // que começam com um prefixo por busca binária (`name_tokens_with_prefix`)
+    // em vez de escanear `name_index` inteiro a cada token de consulta.
+    name_vocabulary: Vec<String>,
+    // Um builder cacheado por distância de edição suportada (0, 1 e 2), para
+    // não recriar o builder a cada chamada de search_fuzzy; o autômato da
+    // consulta em si é montado a cada chamada.
+    fuzzy_builders: [LevenshteinAutomatonBuilder; 3],
+    // Função usada para dobrar nomes/categorias/marcas antes de indexar ou
+    // comparar, para que a normalização seja simétrica entre índice e consulta.
+    normalizer: Normalizer,
+    // Formas normalizadas de cada produto, paralelas a `products`, para que a
+    // busca linear compare de forma consistente com os índices. Os campos de
+    // `Product` continuam intactos para exibição.
+    normalized_names: Vec<String>,
+    normalized_categories: Vec<String>,
+    normalized_brands: Vec<String>,
+    // Sinônimos de token -> alternativas, usados para expandir a consulta
+    // antes de casar contra os índices (ex.: "notebook" -> ["laptop"]).
+    synonyms: HashMap<String, Vec<String>>,
 }
 
 impl Store {
     fn new(products: Vec<Product>) -> Self {
+        Self::with_normalization(products, default_normalizer)
+    }
+
+    // Mesma construção de `new`, mas com a função de normalização escolhida
+    // pelo chamador (ex.: um fold mais agressivo, ou nenhum fold).
+    fn with_normalization(products: Vec<Product>, normalizer: Normalizer) -> Self {
+        let normalized_names = products.iter().map(|p| normalizer(&p.name)).collect();
+        let normalized_categories = products.iter().map(|p| normalizer(&p.category)).collect();
+        let normalized_brands = products.iter().map(|p| normalizer(&p.brand)).collect();
+
         let mut store = Store {
             products,
             name_index: HashMap::new(),
             category_index: HashMap::new(),
             brand_index: HashMap::new(),
+            name_vocabulary: Vec::new(),
+            fuzzy_builders: [
+                LevenshteinAutomatonBuilder::new(0),
+                LevenshteinAutomatonBuilder::new(1),
+                LevenshteinAutomatonBuilder::new(2),
+            ],
+            normalizer,
+            normalized_names,
+            normalized_categories,
+            normalized_brands,
+            synonyms: HashMap::new(),
         };
         store.build_indices();
         store
     }
 
     fn build_indices(&mut self) {
-        for (i, product) in self.products.iter().enumerate() {
-            self.name_index
-                .entry(product.name.clone())
-                .or_insert(Vec::new())
-                .push(i);
+        for i in 0..self.products.len() {
+            // Índice invertido por palavra: cada token do nome normalizado
+            // aponta para os produtos que o contêm, permitindo consultas de
+            // múltiplas palavras, de uma palavra só, e sem diacríticos.
+            for token in tokenize(&self.normalized_names[i]) {
+                let postings = self.name_index.entry(token).or_insert(Vec::new());
+                if !postings.contains(&i) {
+                    postings.push(i);
+                }
+            }
             self.category_index
-                .entry(product.category.clone())
+                .entry(self.normalized_categories[i].clone())
                 .or_insert(Vec::new())
                 .push(i);
             self.brand_index
-                .entry(product.brand.clone())
+                .entry(self.normalized_brands[i].clone())
                 .or_insert(Vec::new())
                 .push(i);
         }
+        self.name_vocabulary = self.name_index.keys().cloned().collect();
+        self.name_vocabulary.sort();
+    }
+
+    // Palavras de `name_vocabulary` que começam com `prefix`, por busca
+    // binária sobre o vocabulário ordenado (O(log V + k) em vez de escanear
+    // `name_index` inteiro). Inclui `prefix` em si quando ele próprio é uma
+    // palavra do vocabulário, já que toda palavra é prefixo de si mesma.
+    fn name_tokens_with_prefix(&self, prefix: &str) -> &[String] {
+        let start = self.name_vocabulary.partition_point(|token| token.as_str() < prefix);
+        let end = start
+            + self.name_vocabulary[start..].partition_point(|token| token.starts_with(prefix));
+        &self.name_vocabulary[start..end]
+    }
+
+    // Define os sinônimos usados para expandir tokens de consulta. As chaves
+    // e os valores são normalizados com o mesmo normalizador dos índices,
+    // para que "notebook" e "Notebook" expandam igual.
+    fn set_synonyms(&mut self, synonyms: HashMap<String, Vec<String>>) {
+        self.synonyms = synonyms
+            .into_iter()
+            .map(|(token, alternatives)| {
+                let token = (self.normalizer)(&token);
+                let alternatives = alternatives.iter().map(|alt| (self.normalizer)(alt)).collect();
+                (token, alternatives)
+            })
+            .collect();
+    }
+
+    // Expande um token normalizado nele mesmo mais seus sinônimos diretos
+    // (sem transitividade: um sinônimo de sinônimo não é incluído).
+    fn expand_token(&self, token: &str) -> Vec<String> {
+        let mut alternatives = vec![token.to_string()];
+        if let Some(synonyms) = self.synonyms.get(token) {
+            for synonym in synonyms {
+                if !alternatives.contains(synonym) {
+                    alternatives.push(synonym.clone());
+                }
+            }
+        }
+        alternatives
+    }
+
+    // Aplica o normalizador da loja ao valor interno de um `Filter::Exact`,
+    // deixando `Filter::Any` intacto.
+    fn normalize_filter(&self, filter: &Filter) -> Filter {
+        match filter {
+            Filter::Any => Filter::Any,
+            Filter::Exact(value) => Filter::Exact((self.normalizer)(value)),
+        }
     }
 
     // Função de busca corrigida - busca combinada (AND entre critérios)
-    fn search(&self, query: &str, category: &str, brand: &str) -> Vec<Product> {
+    fn search(&self, query: &str, category: &Filter, brand: &Filter) -> Vec<Product> {
         let mut results = Vec::new();
-        
+
         // Se todos os parâmetros estão vazios, retorna vazio
-        if query.is_empty() && category.is_empty() && brand.is_empty() {
+        if query.is_empty() && matches!(category, Filter::Any) && matches!(brand, Filter::Any) {
             return results;
         }
 
+        // Cada token da consulta é expandido em si mesmo + seus sinônimos;
+        // o produto casa se, para cada token, alguma alternativa aparecer no nome.
+        let query_tokens = tokenize(&(self.normalizer)(query));
+        // Normaliza o filtro uma vez, para comparar com as formas normalizadas
+        // já guardadas por produto (mesma normalização usada nos índices).
+        let normalized_category = self.normalize_filter(category);
+        let normalized_brand = self.normalize_filter(brand);
+
         // Percorre todos os produtos e verifica se atendem aos critérios
-        for product in &self.products {
+        for (i, product) in self.products.iter().enumerate() {
             let mut matches = true;
-            
+
             // Verifica nome se query não estiver vazia
             if !query.is_empty() {
-                matches = matches && product.name.contains(query);
-            }
-            
-            // Verifica categoria se category não estiver vazia
-            if !category.is_empty() {
-                matches = matches && product.category == category;
+                matches = matches
+                    && !query_tokens.is_empty()
+                    && query_tokens.iter().all(|token| {
+                        self.expand_token(token)
+                            .iter()
+                            .any(|alternative| self.normalized_names[i].contains(alternative.as_str()))
+                    });
             }
-            
-            // Verifica marca se brand não estiver vazia
-            if !brand.is_empty() {
-                matches = matches && product.brand == brand;
-            }
-            
+
+            // Verifica categoria: `Filter::Any` sempre bate, `Filter::Exact` exige igualdade
+            matches = matches && normalized_category.matches_value(&self.normalized_categories[i]);
+
+            // Verifica marca: `Filter::Any` sempre bate, `Filter::Exact` exige igualdade
+            matches = matches && normalized_brand.matches_value(&self.normalized_brands[i]);
+
             if matches {
                 results.push(product.clone());
             }
         }
-        
+
         results
     }
 
     // Busca otimizada usando os índices
-    fn search_optimized(&self, query: &str, category: &str, brand: &str) -> Vec<Product> {
+    fn search_optimized(&self, query: &str, category: &Filter, brand: &Filter) -> Vec<Product> {
+        match self.optimized_candidate_indices(query, category, brand) {
+            Some(indices) => indices.iter().map(|&i| self.products[i].clone()).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Calcula, usando os índices, o conjunto de índices de produtos que
+    // atendem à consulta/categoria/marca. `None` significa "nenhum candidato"
+    // (consulta vazia em tudo, ou algum critério sem nenhum produto
+    // correspondente) — compartilhado por `search_optimized` e `search_within`.
+    fn optimized_candidate_indices(
+        &self,
+        query: &str,
+        category: &Filter,
+        brand: &Filter,
+    ) -> Option<Vec<usize>> {
         let mut candidate_indices: Option<Vec<usize>> = None;
 
-        // Buscar por nome usando índice
+        // Buscar por nome usando o índice invertido por palavra: cada token
+        // da consulta restringe ainda mais os candidatos (semântica AND entre
+        // tokens), mas cada token é expandido em si mesmo + seus sinônimos e
+        // casa se QUALQUER alternativa estiver no índice (semântica OR).
         if !query.is_empty() {
-            if let Some(indices) = self.name_index.get(query) {
-                candidate_indices = Some(indices.clone());
-            } else {
-                return Vec::new(); // Se nome específico não existe, retorna vazio
+            for token in tokenize(&(self.normalizer)(query)) {
+                let mut token_indices: Vec<usize> = Vec::new();
+                let mut matched_any_alternative = false;
+                for alternative in self.expand_token(&token) {
+                    if let Some(indices) = self.name_index.get(&alternative) {
+                        matched_any_alternative = true;
+                        for &index in indices {
+                            if !token_indices.contains(&index) {
+                                token_indices.push(index);
+                            }
+                        }
+                    }
+                }
+                if !matched_any_alternative {
+                    return None; // Nenhuma alternativa da palavra existe
+                }
+                if let Some(ref mut candidates) = candidate_indices {
+                    candidates.retain(|index| token_indices.contains(index));
+                } else {
+                    candidate_indices = Some(token_indices);
+                }
             }
         }
 
         // Filtrar por categoria
-        if !category.is_empty() {
-            if let Some(cat_indices) = self.category_index.get(category) {
+        if let Filter::Exact(category_value) = category {
+            if let Some(cat_indices) = self.category_index.get(&(self.normalizer)(category_value)) {
                 if let Some(ref mut candidates) = candidate_indices {
                     // Intersecção: manter apenas produtos que estão em ambas as listas
                     candidates.retain(|&index| cat_indices.contains(&index));
@@ -104,13 +518,13 @@ impl Store {
                     candidate_indices = Some(cat_indices.clone());
                 }
             } else {
-                return Vec::new(); // Categoria não existe
+                return None; // Categoria não existe
             }
         }
 
         // Filtrar por marca
-        if !brand.is_empty() {
-            if let Some(brand_indices) = self.brand_index.get(brand) {
+        if let Filter::Exact(brand_value) = brand {
+            if let Some(brand_indices) = self.brand_index.get(&(self.normalizer)(brand_value)) {
                 if let Some(ref mut candidates) = candidate_indices {
                     // Intersecção: manter apenas produtos que estão em ambas as listas
                     candidates.retain(|&index| brand_indices.contains(&index));
@@ -118,22 +532,188 @@ impl Store {
                     candidate_indices = Some(brand_indices.clone());
                 }
             } else {
-                return Vec::new(); // Marca não existe
+                return None; // Marca não existe
             }
         }
 
-        // Se não há candidatos, retorna vazio
-        let indices = match candidate_indices {
+        candidate_indices
+    }
+
+    // Igual a `search_optimized`, mas restringe o conjunto final de
+    // candidatos aos índices presentes em `allowed` — útil para buscar
+    // dentro de um subconjunto arbitrário de produtos (ex.: favoritos).
+    fn search_within(
+        &self,
+        query: &str,
+        category: &Filter,
+        brand: &Filter,
+        allowed: &[usize],
+    ) -> Vec<Product> {
+        let indices = match self.optimized_candidate_indices(query, category, brand) {
             Some(indices) => indices,
             None => return Vec::new(),
         };
 
-        // Converte índices em produtos
         indices
+            .into_iter()
+            .filter(|index| allowed.contains(index))
+            .map(|index| self.products[index].clone())
+            .collect()
+    }
+
+    // Busca tolerante a erros de digitação: casa `query` contra cada palavra
+    // do nome do produto usando um autômato de Levenshtein montado pelo
+    // builder cacheado para a distância pedida (`max_distance`, limitada a 2,
+    // a maior suportada).
+    //
+    // `max_distance` é respeitado exatamente como informado, sem nenhum teto
+    // automático: para consultas muito curtas (ex.: 1-2 caracteres) com
+    // `max_distance >= 1`, isso pode legitimamente casar com qualquer palavra
+    // cujo primeiro caractere difira, já que a distância de edição entre dois
+    // caracteres quaisquer já é <= 1 — não é um bug do autômato, é inerente a
+    // pedir um orçamento de edição grande relativo ao tamanho da consulta.
+    // Para esse caso, prefira `search_fuzzy_auto`, que reduz automaticamente
+    // o orçamento para consultas curtas.
+    fn search_fuzzy(&self, query: &str, max_distance: u8) -> Vec<Product> {
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        // Mesma normalização usada pelos demais métodos de busca, para que
+        // diferenças de caixa/acento não consumam o orçamento de distância.
+        let normalized_query = (self.normalizer)(query);
+
+        let builder = &self.fuzzy_builders[max_distance.min(2) as usize];
+        let dfa = builder.build_dfa(&normalized_query, true);
+
+        self.products
             .iter()
-            .map(|&i| self.products[i].clone())
+            .enumerate()
+            .filter(|(i, _)| {
+                self.normalized_names[*i]
+                    .split_whitespace()
+                    .any(|word| dfa.is_match(word))
+            })
+            .map(|(_, product)| product.clone())
             .collect()
     }
+
+    // Igual a `search_fuzzy`, mas reduz automaticamente o `max_distance`
+    // pedido conforme o tamanho da consulta (0 para consultas de até 4
+    // caracteres, 1 até 8, senão o valor pedido) antes de montar o autômato.
+    // Existe como alternativa explícita a `search_fuzzy` para chamadores que
+    // preferem essa proteção contra o efeito descrito acima, em vez de tê-la
+    // aplicada silenciosamente sobre o parâmetro que informaram.
+    fn search_fuzzy_auto(&self, query: &str, max_distance: u8) -> Vec<Product> {
+        let effective_distance =
+            max_distance.min(typo_budget_for_length((self.normalizer)(query).chars().count()));
+        self.search_fuzzy(query, effective_distance)
+    }
+
+    // Busca por relevância: reúne todo produto com ao menos uma palavra do nome
+    // igual a uma palavra da consulta ou começando por ela, e os ordena pelos
+    // critérios de ranqueamento padrão.
+    fn search_ranked(&self, query: &str, category: &str, brand: &str) -> Vec<Product> {
+        self.search_ranked_with(query, category, brand, &Criteria::default_criteria())
+    }
+
+    // Igual a `search_ranked`, mas com uma cadeia de critérios customizada,
+    // permitindo ao chamador trocar ou reordenar os critérios de relevância.
+    fn search_ranked_with(
+        &self,
+        query: &str,
+        category: &str,
+        brand: &str,
+        criteria: &Criteria,
+    ) -> Vec<Product> {
+        let query_tokens = tokenize(&(self.normalizer)(query));
+        if query_tokens.is_empty() {
+            return Vec::new();
+        }
+
+        let normalized_category = (self.normalizer)(category);
+        let normalized_brand = (self.normalizer)(brand);
+
+        // União dos produtos que contêm ao menos uma palavra da consulta, seja
+        // por igualdade exata ou porque a palavra da consulta é prefixo de uma
+        // palavra do nome (ex.: consulta "lap" deve trazer "Laptop" como
+        // candidato, para que `match_metrics` possa reconhecer o casamento por
+        // prefixo em vez de descartá-lo antes mesmo de chegar lá).
+        // `name_tokens_with_prefix` já inclui o próprio token quando ele é uma
+        // palavra do vocabulário, cobrindo o caso de igualdade exata também.
+        let mut candidate_indices: Vec<usize> = Vec::new();
+        for token in &query_tokens {
+            for name_token in self.name_tokens_with_prefix(token) {
+                if let Some(indices) = self.name_index.get(name_token) {
+                    for &index in indices {
+                        if !candidate_indices.contains(&index) {
+                            candidate_indices.push(index);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut ranked: Vec<RankedProduct> = candidate_indices
+            .into_iter()
+            .filter(|&index| category.is_empty() || self.normalized_categories[index] == normalized_category)
+            .filter(|&index| brand.is_empty() || self.normalized_brands[index] == normalized_brand)
+            .map(|index| RankedProduct {
+                product: self.products[index].clone(),
+                metrics: self.match_metrics(index, &query_tokens),
+            })
+            .collect();
+
+        ranked.sort_by(|a, b| criteria.compare(a, b));
+        ranked.into_iter().map(|ranked| ranked.product).collect()
+    }
+
+    // Calcula os metadados de casamento de um produto (pelo índice) contra os
+    // tokens normalizados da consulta: quantos casaram (exatamente ou por
+    // prefixo), a distância acumulada dos que só casaram por edição, e a
+    // posição do primeiro casamento. Cada token é testado nesta ordem: igual
+    // exato, depois prefixo de alguma palavra do nome, e só então a palavra
+    // mais próxima por distância de edição.
+    fn match_metrics(&self, product_index: usize, query_tokens: &[String]) -> MatchMetrics {
+        let name_tokens = tokenize(&self.normalized_names[product_index]);
+        let mut matched_tokens = 0;
+        let mut exact_tokens = 0;
+        let mut total_distance = 0u32;
+        let mut first_match_offset = usize::MAX;
+
+        for query_token in query_tokens {
+            if let Some(position) = name_tokens.iter().position(|token| token == query_token) {
+                matched_tokens += 1;
+                exact_tokens += 1;
+                first_match_offset = first_match_offset.min(position);
+            } else if let Some(position) = name_tokens
+                .iter()
+                .position(|token| token.starts_with(query_token.as_str()))
+            {
+                matched_tokens += 1;
+                first_match_offset = first_match_offset.min(position);
+            } else if let Some((position, distance)) = name_tokens
+                .iter()
+                .enumerate()
+                .map(|(position, token)| (position, edit_distance(query_token, token)))
+                .min_by_key(|&(_, distance)| distance)
+            {
+                total_distance += distance;
+                first_match_offset = first_match_offset.min(position);
+            }
+        }
+
+        MatchMetrics {
+            matched_tokens,
+            exact_tokens,
+            total_distance,
+            first_match_offset: if first_match_offset == usize::MAX {
+                0
+            } else {
+                first_match_offset
+            },
+        }
+    }
 }
 
 fn main() {
@@ -161,13 +741,13 @@ fn main() {
         },
     ];
 
-    let store = Store::new(products);
+    let mut store = Store::new(products);
 
     println!("=== EXEMPLOS DE BUSCA ===\n");
 
     // Exemplo 1: Busca por nome específico
     println!("1. Busca por nome 'Laptop':");
-    let results = store.search("Laptop", "", "");
+    let results = store.search("Laptop", &Filter::Any, &Filter::Any);
     for product in &results {
         println!("   {:?}", product);
     }
@@ -175,7 +755,7 @@ fn main() {
 
     // Exemplo 2: Busca por categoria
     println!("2. Busca por categoria 'Eletrônicos':");
-    let results = store.search("", "Eletrônicos", "");
+    let results = store.search("", &Filter::Exact("Eletrônicos".to_string()), &Filter::Any);
     for product in &results {
         println!("   {:?}", product);
     }
@@ -183,7 +763,7 @@ fn main() {
 
     // Exemplo 3: Busca por marca
     println!("3. Busca por marca 'MarcaC':");
-    let results = store.search("", "", "MarcaC");
+    let results = store.search("", &Filter::Any, &Filter::Exact("MarcaC".to_string()));
     for product in &results {
         println!("   {:?}", product);
     }
@@ -191,7 +771,11 @@ fn main() {
 
     // Exemplo 4: Busca combinada (nome + categoria + marca)
     println!("4. Busca combinada (Laptop + Eletrônicos + MarcaA):");
-    let results = store.search("Laptop", "Eletrônicos", "MarcaA");
+    let results = store.search(
+        "Laptop",
+        &Filter::Exact("Eletrônicos".to_string()),
+        &Filter::Exact("MarcaA".to_string()),
+    );
     for product in &results {
         println!("   {:?}", product);
     }
@@ -199,7 +783,7 @@ fn main() {
 
     // Exemplo 5: Busca que não encontra nada
     println!("5. Busca que não encontra (produto inexistente):");
-    let results = store.search("Tablet", "", "");
+    let results = store.search("Tablet", &Filter::Any, &Filter::Any);
     for product in &results {
         println!("   {:?}", product);
     }
@@ -207,12 +791,247 @@ fn main() {
 
     // Comparando busca normal vs otimizada
     println!("=== COMPARAÇÃO: BUSCA NORMAL vs OTIMIZADA ===\n");
-    
+
+    let moveis = Filter::Exact("Móveis".to_string());
     println!("Busca normal por categoria 'Móveis':");
-    let results1 = store.search("", "Móveis", "");
+    let results1 = store.search("", &moveis, &Filter::Any);
     println!("   Encontrados: {} produto(s)", results1.len());
-    
+
     println!("Busca otimizada por categoria 'Móveis':");
-    let results2 = store.search_optimized("", "Móveis", "");
+    let results2 = store.search_optimized("", &moveis, &Filter::Any);
     println!("   Encontrados: {} produto(s)", results2.len());
+
+    // Exemplo 6: Busca tolerante a erros de digitação
+    println!("\n=== BUSCA FUZZY (TOLERANTE A ERROS) ===\n");
+    println!("Busca fuzzy por 'Laptp' (distância máxima 1):");
+    let results = store.search_fuzzy("Laptp", 1);
+    for product in &results {
+        println!("   {:?}", product);
+    }
+    println!("   Encontrados: {} produto(s)\n", results.len());
+
+    println!("Busca fuzzy automática por 'a' (distância máxima 1, reduzida pelo tamanho da consulta):");
+    let results = store.search_fuzzy_auto("a", 1);
+    for product in &results {
+        println!("   {:?}", product);
+    }
+    println!("   Encontrados: {} produto(s)\n", results.len());
+
+    // Exemplo 7: Busca ranqueada por relevância
+    println!("=== BUSCA RANQUEADA ===\n");
+    println!("Busca ranqueada por 'Mesa':");
+    let results = store.search_ranked("Mesa", "", "");
+    for product in &results {
+        println!("   {:?}", product);
+    }
+    println!("   Encontrados: {} produto(s)\n", results.len());
+
+    // Exemplo 8: Busca sem diacríticos
+    println!("=== BUSCA SEM ACENTOS ===\n");
+    println!("Busca por categoria 'Eletronicos' (sem acento):");
+    let results = store.search_optimized("", &Filter::Exact("Eletronicos".to_string()), &Filter::Any);
+    for product in &results {
+        println!("   {:?}", product);
+    }
+    println!("   Encontrados: {} produto(s)\n", results.len());
+
+    // Exemplo 9: Busca com sinônimos
+    println!("=== BUSCA COM SINÔNIMOS ===\n");
+    store.set_synonyms(HashMap::from([(
+        "notebook".to_string(),
+        vec!["laptop".to_string()],
+    )]));
+    println!("Busca por 'notebook' (sinônimo de 'Laptop'):");
+    let results = store.search_optimized("notebook", &Filter::Any, &Filter::Any);
+    for product in &results {
+        println!("   {:?}", product);
+    }
+    println!("   Encontrados: {} produto(s)\n", results.len());
+
+    // Exemplo 10: Busca restrita a um subconjunto de produtos
+    println!("=== BUSCA DENTRO DE UM SUBCONJUNTO ===\n");
+    println!("Busca por categoria 'Móveis' restrita aos índices [0, 3] (apenas 'Mesa'):");
+    let results = store.search_within("", &Filter::Exact("Móveis".to_string()), &Filter::Any, &[0, 3]);
+    for product in &results {
+        println!("   {:?}", product);
+    }
+    println!("   Encontrados: {} produto(s)\n", results.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_store() -> Store {
+        Store::new(vec![
+            Product {
+                name: "Laptop".to_string(),
+                category: "Eletrônicos".to_string(),
+                brand: "MarcaA".to_string(),
+            },
+            Product {
+                name: "Smartphone".to_string(),
+                category: "Eletrônicos".to_string(),
+                brand: "MarcaB".to_string(),
+            },
+            Product {
+                name: "Cadeira".to_string(),
+                category: "Móveis".to_string(),
+                brand: "MarcaC".to_string(),
+            },
+            Product {
+                name: "Mesa".to_string(),
+                category: "Móveis".to_string(),
+                brand: "MarcaC".to_string(),
+            },
+        ])
+    }
+
+    // Regressão: uma consulta cujo tamanho é <= max_distance não pode casar
+    // com qualquer nome de produto só porque o estado inicial do autômato
+    // aceita trivialmente a palavra vazia. `search_fuzzy_auto` existe
+    // justamente para reduzir o orçamento nesses casos.
+    #[test]
+    fn search_fuzzy_auto_does_not_match_unrelated_names_for_short_queries() {
+        let store = sample_store();
+
+        let results = store.search_fuzzy_auto("a", 1);
+        assert!(!results.iter().any(|p| p.name == "Smartphone"));
+        assert!(!results.iter().any(|p| p.name == "Cadeira"));
+
+        let results = store.search_fuzzy_auto("ab", 2);
+        assert!(!results.iter().any(|p| p.name == "Smartphone"));
+        assert!(!results.iter().any(|p| p.name == "Cadeira"));
+    }
+
+    // Regressão: `search_fuzzy` deve respeitar o `max_distance` exatamente
+    // como informado, sem nenhum teto automático por tamanho de consulta —
+    // "Labrop" (6 caracteres) está a distância de edição 2 de "Laptop", e um
+    // chamador que pediu `max_distance = 2` precisa receber esse resultado.
+    #[test]
+    fn search_fuzzy_honors_caller_requested_max_distance() {
+        let store = sample_store();
+
+        let results = store.search_fuzzy("Labrop", 2);
+        assert!(results.iter().any(|p| p.name == "Laptop"));
+    }
+
+    // Um casamento exato de token deve ranquear acima de um casamento que só
+    // é prefixo, mesmo que ambos contem como "casados".
+    #[test]
+    fn search_ranked_prefers_exact_token_match_over_prefix_match() {
+        let store = Store::new(vec![
+            Product {
+                name: "Lap".to_string(),
+                category: "Eletrônicos".to_string(),
+                brand: "MarcaA".to_string(),
+            },
+            Product {
+                name: "Laptop".to_string(),
+                category: "Eletrônicos".to_string(),
+                brand: "MarcaA".to_string(),
+            },
+        ]);
+
+        let results = store.search_ranked("Lap", "", "");
+        assert_eq!(results[0].name, "Lap");
+        assert_eq!(results[1].name, "Laptop");
+    }
+
+    // O índice invertido de `search_optimized` é por palavra inteira, não por
+    // substring: "top" não deve casar com "Laptop" (diferente de `search`,
+    // que usa `contains` sobre o nome inteiro), mas a palavra completa casa.
+    #[test]
+    fn search_optimized_matches_whole_words_not_substrings() {
+        let store = sample_store();
+
+        let results = store.search_optimized("top", &Filter::Any, &Filter::Any);
+        assert!(results.is_empty());
+
+        let results = store.search_optimized("laptop", &Filter::Any, &Filter::Any);
+        assert!(results.iter().any(|p| p.name == "Laptop"));
+    }
+
+    // Regressão: filtrar por categoria sem o acento da consulta ("Eletronicos")
+    // ainda deve encontrar produtos cuja categoria tem acento ("Eletrônicos"),
+    // já que ambos passam pelo mesmo normalizador antes de comparar.
+    #[test]
+    fn search_optimized_category_filter_ignores_accents() {
+        let store = sample_store();
+
+        let results = store.search_optimized(
+            "",
+            &Filter::Exact("Eletronicos".to_string()),
+            &Filter::Any,
+        );
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().any(|p| p.name == "Laptop"));
+        assert!(results.iter().any(|p| p.name == "Smartphone"));
+    }
+
+    // Um token de consulta com sinônimo cadastrado deve casar com produtos
+    // que só contêm a alternativa, não o termo original.
+    #[test]
+    fn search_optimized_expands_query_tokens_via_synonyms() {
+        let mut store = sample_store();
+        store.set_synonyms(HashMap::from([(
+            "notebook".to_string(),
+            vec!["laptop".to_string()],
+        )]));
+
+        let results = store.search_optimized("notebook", &Filter::Any, &Filter::Any);
+        assert!(results.iter().any(|p| p.name == "Laptop"));
+    }
+
+    // A expansão de sinônimos não é transitiva: um sinônimo de um sinônimo
+    // não deve ser incluído nas alternativas.
+    #[test]
+    fn expand_token_does_not_follow_transitive_synonyms() {
+        let mut store = sample_store();
+        store.set_synonyms(HashMap::from([
+            ("notebook".to_string(), vec!["laptop".to_string()]),
+            ("laptop".to_string(), vec!["ultrabook".to_string()]),
+        ]));
+
+        let alternatives = store.expand_token("notebook");
+        assert!(alternatives.contains(&"laptop".to_string()));
+        assert!(!alternatives.contains(&"ultrabook".to_string()));
+    }
+
+    // `search_within` deve restringir o resultado final ao subconjunto
+    // `allowed`, mesmo quando mais produtos atenderiam à consulta/filtro.
+    #[test]
+    fn search_within_restricts_results_to_allowed_subset() {
+        let store = sample_store();
+
+        // Sem restrição, "Móveis" casa com "Cadeira" (índice 2) e "Mesa" (índice 3).
+        let unrestricted =
+            store.search_optimized("", &Filter::Exact("Móveis".to_string()), &Filter::Any);
+        assert_eq!(unrestricted.len(), 2);
+
+        // Restrito a [0, 3], só "Mesa" (índice 3) sobrevive; "Cadeira" (índice
+        // 2) fica de fora mesmo batendo com a categoria.
+        let restricted =
+            store.search_within("", &Filter::Exact("Móveis".to_string()), &Filter::Any, &[0, 3]);
+        assert_eq!(restricted.len(), 1);
+        assert_eq!(restricted[0].name, "Mesa");
+    }
+
+    // `Filter::Any` não restringe nada; `Filter::Exact` exige igualdade com o
+    // valor normalizado.
+    #[test]
+    fn filter_any_matches_everything_exact_requires_equality() {
+        let store = sample_store();
+
+        let all_brands = store.search("", &Filter::Exact("Eletrônicos".to_string()), &Filter::Any);
+        assert_eq!(all_brands.len(), 2);
+
+        let exact_brand = store.search(
+            "",
+            &Filter::Exact("Eletrônicos".to_string()),
+            &Filter::Exact("MarcaB".to_string()),
+        );
+        assert_eq!(exact_brand.len(), 1);
+        assert_eq!(exact_brand[0].name, "Smartphone");
+    }
 }
\ No newline at end of file